@@ -9,6 +9,15 @@ const FORTRAN_LIB: &str = "flang";
 #[cfg(feature = "intel")]
 const FORTRAN_LIB: &str = "ifcore";
 
+/// The PRIMA repository cloned when no source override is provided.
+const PRIMA_REPOSITORY: &str = "https://github.com/libprima/prima";
+
+/// The PRIMA revision (commit or tag) built by default. Pinning a known-good
+/// revision keeps the generated bindings stable and builds reproducible
+/// instead of tracking a moving `HEAD`. Override it with the `PRIMA_REV`
+/// environment variable.
+const DEFAULT_PRIMA_REV: &str = "v0.7.4";
+
 fn main() {
     // Skip building the bindings if we are on docs.rs, otherwise we
     // will get build failures because their images won't contain our
@@ -18,6 +27,8 @@ fn main() {
     }
 
     println!("cargo::rerun-if-changed=prima_bindgen.h");
+    println!("cargo::rerun-if-env-changed=PRIMA_REV");
+    println!("cargo::rerun-if-env-changed=PRIMA_SOURCE_DIR");
 
     // We will need a Fortran compiler to build to PRIMA library.
     if std::env::var("FC").is_err() {
@@ -26,25 +37,26 @@ fn main() {
 
     let out_path = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
-    // Clone PRIMA from GitHub.
-    let mut fetch_options = git2::FetchOptions::new();
-    fetch_options.depth(1);
-    let mut builder = git2::build::RepoBuilder::new();
-    builder.fetch_options(fetch_options);
-    let prima_clone_path = out_path.join("prima");
-    builder.clone("https://github.com/libprima/prima", &prima_clone_path)
-        .expect("Failed to clone PRIMA.");
+    // Resolve the PRIMA source tree. `cleanup` is `Some` when we created a
+    // directory ourselves (a fresh clone or an extracted tarball) and are
+    // therefore responsible for deleting it after the build; it points at the
+    // top-level directory we created, not at the nested source root we build
+    // from.
+    let (prima_source_path, cleanup) = prima_source(&out_path);
 
     // Build the PRIMA library with CMake.
-    let dst = cmake::Config::new(&prima_clone_path)
+    let dst = cmake::Config::new(&prima_source_path)
         .define("BUILD_SHARED_LIBS", "ON")
         .build();
 
-    // Delete the cloned PRIMA repository after building the library.
-    // Otherwise we will get errors in subsequent builds when git clone
-    // tries to clone into a directory that already exists.
-    std::fs::remove_dir_all(&prima_clone_path)
-        .expect("Failed to delete cloned prima repository after build.");
+    // Delete the tree only if we created it. Otherwise we will get errors in
+    // subsequent builds when the repository is set up again (its `origin`
+    // remote added a second time) in a directory that already exists; a
+    // user-supplied `PRIMA_SOURCE_DIR` is left untouched.
+    if let Some(cleanup_path) = cleanup {
+        std::fs::remove_dir_all(&cleanup_path)
+            .expect("Failed to delete PRIMA source tree after build.");
+    }
 
     println!("cargo:rustc-link-search=native={}/lib", dst.display());
     println!("cargo:rustc-link-lib=primac");
@@ -64,3 +76,114 @@ fn main() {
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Failed to write PRIMA C bindings to file.");
 }
+
+/// Locates the PRIMA source tree to build from, in order of precedence:
+///
+/// 1. `PRIMA_SOURCE_DIR` - build from a local checkout and skip any download,
+///    which is what air-gapped and offline builds need.
+/// 2. the `vendored` feature - extract a tarball shipped inside the crate.
+/// 3. otherwise clone the repository at the pinned revision.
+///
+/// Returns the source root to build from together with an optional directory
+/// to delete once the build is done. The cleanup path is the top-level
+/// directory we created (`OUT_DIR/prima`), which may differ from the source
+/// root when a tarball unpacks into a nested directory; a user-supplied
+/// `PRIMA_SOURCE_DIR` is never cleaned up.
+fn prima_source(out_path: &std::path::Path) -> (std::path::PathBuf, Option<std::path::PathBuf>) {
+    if let Ok(source_dir) = std::env::var("PRIMA_SOURCE_DIR") {
+        return (std::path::PathBuf::from(source_dir), None);
+    }
+
+    #[cfg(feature = "vendored")]
+    {
+        let extract_path = out_path.join("prima");
+        (extract_vendored_prima(&extract_path), Some(extract_path))
+    }
+
+    #[cfg(not(feature = "vendored"))]
+    {
+        let clone_path = out_path.join("prima");
+        (clone_prima(&clone_path), Some(clone_path))
+    }
+}
+
+/// Fetches PRIMA at the pinned revision (overridable with the `PRIMA_REV`
+/// environment variable) and checks it out at `clone_path`.
+///
+/// We fetch the requested ref by name rather than shallow-cloning `HEAD`: a
+/// `depth(1)` clone only downloads the default-branch tip, so an older release
+/// tag such as the default `v0.7.4` would not be present and could not be
+/// resolved. Fetching the ref directly keeps the default path shallow *and*
+/// reproducible.
+#[cfg(not(feature = "vendored"))]
+fn clone_prima(clone_path: &std::path::Path) -> std::path::PathBuf {
+    let revision = std::env::var("PRIMA_REV").unwrap_or_else(|_| DEFAULT_PRIMA_REV.to_string());
+
+    let repository =
+        git2::Repository::init(clone_path).expect("Failed to initialise PRIMA repository.");
+    let mut remote = repository
+        .remote("origin", PRIMA_REPOSITORY)
+        .expect("Failed to add PRIMA remote.");
+
+    // Fetch the pinned ref (tag or branch) at shallow depth, downloading all of
+    // the remote's tags as well so the revision is guaranteed to be present.
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.depth(1);
+    fetch_options.download_tags(git2::AutotagOption::All);
+    remote
+        .fetch(&[&revision], Some(&mut fetch_options), None)
+        .unwrap_or_else(|_| panic!("Failed to fetch PRIMA revision '{revision}'."));
+
+    // Resolve what we just fetched (`FETCH_HEAD`) and check it out detached so
+    // the build always uses exactly the pinned revision.
+    let fetch_head = repository
+        .find_reference("FETCH_HEAD")
+        .unwrap_or_else(|_| panic!("Failed to resolve PRIMA revision '{revision}'."));
+    let object = fetch_head
+        .peel(git2::ObjectType::Commit)
+        .expect("Failed to peel fetched PRIMA revision to a commit.");
+    repository
+        .checkout_tree(
+            &object,
+            Some(git2::build::CheckoutBuilder::new().force()),
+        )
+        .expect("Failed to check out PRIMA revision.");
+    repository
+        .set_head_detached(object.id())
+        .expect("Failed to set PRIMA HEAD to the pinned revision.");
+
+    clone_path.to_path_buf()
+}
+
+/// Extracts the vendored PRIMA source tarball shipped with the crate into
+/// `extract_path`, enabling fully offline builds with no git dependency.
+///
+/// Returns the PRIMA source root (the nested directory the tarball unpacks
+/// into); the caller retains `extract_path` for cleanup so nothing is left
+/// behind in `OUT_DIR`.
+#[cfg(feature = "vendored")]
+fn extract_vendored_prima(extract_path: &std::path::Path) -> std::path::PathBuf {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let tarball_path = std::path::Path::new(&manifest_dir)
+        .join("vendor")
+        .join("prima.tar.gz");
+    println!("cargo::rerun-if-changed={}", tarball_path.display());
+
+    let tarball = std::fs::File::open(&tarball_path)
+        .expect("Failed to open vendored PRIMA tarball. Is the `vendored` feature set up?");
+    let decoder = flate2::read::GzDecoder::new(tarball);
+    let mut archive = tar::Archive::new(decoder);
+
+    archive
+        .unpack(extract_path)
+        .expect("Failed to extract vendored PRIMA tarball.");
+
+    // The tarball unpacks to a single top-level directory; descend into it so
+    // the returned path points at the PRIMA source root.
+    std::fs::read_dir(extract_path)
+        .expect("Failed to read extracted PRIMA tarball.")
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.is_dir())
+        .unwrap_or_else(|| extract_path.to_path_buf())
+}
@@ -15,6 +15,8 @@
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+pub mod safe;
+
 impl Default for prima_problem_t {
     fn default() -> Self {
         prima_problem_t {
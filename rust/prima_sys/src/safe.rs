@@ -0,0 +1,951 @@
+//! Safe, closure-based wrappers over the raw PRIMA C interface.
+//!
+//! The raw [`prima_minimize`] API drives optimization through
+//! `unsafe extern "C"` callbacks that receive bare `*const f64` pointers, so
+//! every caller has to slice raw memory by hand (see the crate's examples).
+//! This module hides that machinery behind ordinary Rust closures: the
+//! objective is an `FnMut(&[f64]) -> f64` and the objective-plus-constraints
+//! callback is an `FnMut(&[f64]) -> (f64, Vec<f64>)`.
+//!
+//! The closure is boxed and its pointer is threaded through the
+//! `prima_options_t::data` field that PRIMA already forwards to every
+//! callback. A private, monomorphized `extern "C"` trampoline recovers the
+//! closure from that pointer, rebuilds the `&[f64]` argument with
+//! [`std::slice::from_raw_parts`] and writes the results back through the C
+//! out-parameters. The box is owned for the whole duration of the
+//! [`prima_minimize`] call, so the pointer can never dangle.
+//!
+//! ## Unwinding across the FFI boundary
+//!
+//! The supplied closure is invoked from C. Unwinding across that boundary is
+//! undefined behavior, so the closure **must not panic**; if it might, catch
+//! the panic inside the closure (e.g. with [`std::panic::catch_unwind`]) or
+//! abort the process.
+//!
+//! An objective-and-constraints closure must return exactly as many constraint
+//! values as the problem declares (`m`). Returning a different number would
+//! otherwise panic inside the trampoline and unwind across the boundary, so a
+//! mismatch aborts the process instead.
+
+use crate::*;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+
+/// Closure and the variable count it expects, boxed behind
+/// `prima_options_t::data` for the objective trampoline.
+struct Objective<F> {
+    call: F,
+    n: usize,
+}
+
+/// Closure plus the variable and nonlinear-constraint counts, boxed behind
+/// `prima_options_t::data` for the objective-and-constraints trampoline.
+struct ObjectiveAndConstraints<F> {
+    call: F,
+    n: usize,
+    m: usize,
+}
+
+/// Monomorphized `calfun` that recovers the objective closure from `data` and
+/// writes the objective value through `f`.
+unsafe extern "C" fn calfun_trampoline<F>(x: *const f64, f: *mut f64, data: *const c_void)
+where
+    F: FnMut(&[f64]) -> f64,
+{
+    let objective = &mut *(data as *mut Objective<F>);
+    let x = std::slice::from_raw_parts(x, objective.n);
+    *f = (objective.call)(x);
+}
+
+/// Monomorphized `calcfc` that recovers the objective-and-constraints closure
+/// from `data`, writes the objective value through `f` and copies the returned
+/// constraint values into `constr`.
+unsafe extern "C" fn calcfc_trampoline<F>(
+    x: *const f64,
+    f: *mut f64,
+    constr: *mut f64,
+    data: *const c_void,
+) where
+    F: FnMut(&[f64]) -> (f64, Vec<f64>),
+{
+    let objective = &mut *(data as *mut ObjectiveAndConstraints<F>);
+    let x = std::slice::from_raw_parts(x, objective.n);
+    let (value, constraints) = (objective.call)(x);
+    *f = value;
+
+    // The closure must return exactly `m` constraint values. Slicing or
+    // `copy_from_slice` would panic on a short vector, and that panic would
+    // unwind across the C boundary — the undefined behavior this module warns
+    // against. Detect the violation and abort with a defined failure instead.
+    if constraints.len() != objective.m {
+        eprintln!(
+            "prima: constraint closure returned {} values, expected {}",
+            constraints.len(),
+            objective.m
+        );
+        std::process::abort();
+    }
+    if objective.m > 0 {
+        let constr = std::slice::from_raw_parts_mut(constr, objective.m);
+        constr.copy_from_slice(&constraints);
+    }
+}
+
+/// Minimizes an unconstrained objective expressed as a Rust closure.
+///
+/// The closure is called with a slice of the current point and returns the
+/// objective value. On success the optimum is written back into `x0`, which
+/// both supplies the initial guess and receives the result. The PRIMA return
+/// code is returned unchanged.
+///
+/// ## Parameters
+/// - `algorithm`: The PRIMA algorithm to use (e.g. `prima_algorithm_t_PRIMA_NEWUOA`).
+/// - `x0`: The initial guess on input, the optimum on output.
+/// - `objective`: The objective closure `FnMut(&[f64]) -> f64`.
+///
+/// ## Returns
+/// The raw PRIMA status code returned by [`prima_minimize`].
+pub fn minimize<F>(algorithm: prima_algorithm_t, x0: &mut [f64], objective: F) -> i32
+where
+    F: FnMut(&[f64]) -> f64,
+{
+    let n = x0.len();
+
+    let mut problem = new_problem(n as i32);
+    problem.x0 = x0.as_mut_ptr();
+    problem.calfun = Some(calfun_trampoline::<F>);
+
+    // Keep the boxed closure alive for the whole `prima_minimize` call so the
+    // pointer stored in `options.data` stays valid.
+    let mut objective = Box::new(Objective { call: objective, n });
+
+    let mut options = new_options();
+    options.data = (&mut *objective as *mut Objective<F>).cast::<c_void>();
+
+    let mut result = prima_result_t::default();
+    let result_ptr = std::ptr::addr_of_mut!(result);
+    let status = unsafe { prima_minimize(algorithm, problem, options, result_ptr) };
+
+    unsafe {
+        if !result.x.is_null() {
+            x0.copy_from_slice(std::slice::from_raw_parts(result.x, n));
+        }
+        prima_free_result(result_ptr);
+    }
+
+    drop(objective);
+    status
+}
+
+/// Minimizes an objective subject to `num_constraints` nonlinear constraints,
+/// both expressed as a single Rust closure.
+///
+/// The closure is called with a slice of the current point and returns a
+/// `(objective, constraints)` pair, where `constraints` has length
+/// `num_constraints` and follows PRIMA's `constr <= 0` convention. On success
+/// the optimum is written back into `x0`. The PRIMA return code is returned
+/// unchanged.
+///
+/// ## Parameters
+/// - `algorithm`: The PRIMA algorithm to use (e.g. `prima_algorithm_t_PRIMA_COBYLA`).
+/// - `x0`: The initial guess on input, the optimum on output.
+/// - `num_constraints`: The number of nonlinear constraints.
+/// - `objective`: The objective-and-constraints closure `FnMut(&[f64]) -> (f64, Vec<f64>)`.
+///
+/// ## Returns
+/// The raw PRIMA status code returned by [`prima_minimize`].
+pub fn minimize_constrained<F>(
+    algorithm: prima_algorithm_t,
+    x0: &mut [f64],
+    num_constraints: usize,
+    objective: F,
+) -> i32
+where
+    F: FnMut(&[f64]) -> (f64, Vec<f64>),
+{
+    let n = x0.len();
+
+    let mut problem = new_problem(n as i32);
+    problem.x0 = x0.as_mut_ptr();
+    problem.m_nlcon = num_constraints as i32;
+    problem.calcfc = Some(calcfc_trampoline::<F>);
+
+    // PRIMA needs the objective and constraint values at the initial point.
+    // Evaluate the closure once before boxing it, keeping `nlconstr0` alive
+    // until after `prima_minimize` so its pointer stays valid.
+    let mut objective = objective;
+    let (f0, mut nlconstr0) = objective(x0);
+    problem.f0 = f0;
+    problem.nlconstr0 = nlconstr0.as_mut_ptr();
+
+    let mut objective = Box::new(ObjectiveAndConstraints {
+        call: objective,
+        n,
+        m: num_constraints,
+    });
+
+    let mut options = new_options();
+    options.data = (&mut *objective as *mut ObjectiveAndConstraints<F>).cast::<c_void>();
+
+    let mut result = prima_result_t::default();
+    let result_ptr = std::ptr::addr_of_mut!(result);
+    let status = unsafe { prima_minimize(algorithm, problem, options, result_ptr) };
+
+    unsafe {
+        if !result.x.is_null() {
+            x0.copy_from_slice(std::slice::from_raw_parts(result.x, n));
+        }
+        prima_free_result(result_ptr);
+    }
+
+    drop(objective);
+    drop(nlconstr0);
+    status
+}
+
+/// Boxed objective closure, used by the [`Problem`] builder so that the
+/// trampoline can be monomorphized over a single, nameable type.
+type BoxedObjective = Box<dyn FnMut(&[f64]) -> f64>;
+
+/// Boxed objective-and-constraints closure, used by the [`Problem`] builder
+/// when nonlinear constraints are present.
+type BoxedObjectiveAndConstraints = Box<dyn FnMut(&[f64]) -> (f64, Vec<f64>)>;
+
+/// The verbosity of PRIMA's progress printing, mirroring `prima_message_t`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsgLevel {
+    /// Print nothing (`PRIMA_MSG_NONE`).
+    None,
+    /// Print a message on exit (`PRIMA_MSG_EXIT`).
+    Exit,
+    /// Print a message whenever the trust-region radius is reduced (`PRIMA_MSG_RHO`).
+    Rho,
+    /// Print a message on every function evaluation (`PRIMA_MSG_FEVL`).
+    Fevl,
+}
+
+impl MsgLevel {
+    /// Converts to the raw `iprint` value expected by `prima_options_t`.
+    fn to_raw(self) -> i32 {
+        let level = match self {
+            MsgLevel::None => prima_message_t_PRIMA_MSG_NONE,
+            MsgLevel::Exit => prima_message_t_PRIMA_MSG_EXIT,
+            MsgLevel::Rho => prima_message_t_PRIMA_MSG_RHO,
+            MsgLevel::Fevl => prima_message_t_PRIMA_MSG_FEVL,
+        };
+        level as i32
+    }
+}
+
+/// A fluent builder for [`prima_options_t`].
+///
+/// Each setter returns `Self`, so options can be configured in a single
+/// chained expression without touching the raw struct:
+///
+/// ```rust
+/// # use prima_sys::safe::{Options, MsgLevel};
+/// let options = Options::new()
+///     .max_fun(500)
+///     .rho_end(1e-6)
+///     .print(MsgLevel::Exit);
+/// ```
+pub struct Options {
+    raw: prima_options_t,
+}
+
+impl Options {
+    /// Creates a new, initialized options builder (see [`crate::new_options`]).
+    pub fn new() -> Self {
+        Self { raw: new_options() }
+    }
+
+    /// Sets the maximum number of function evaluations (`maxfun`).
+    pub fn max_fun(mut self, max_fun: i32) -> Self {
+        self.raw.maxfun = max_fun;
+        self
+    }
+
+    /// Sets the initial trust-region radius (`rhobeg`).
+    pub fn rho_begin(mut self, rho_begin: f64) -> Self {
+        self.raw.rhobeg = rho_begin;
+        self
+    }
+
+    /// Sets the final trust-region radius (`rhoend`).
+    pub fn rho_end(mut self, rho_end: f64) -> Self {
+        self.raw.rhoend = rho_end;
+        self
+    }
+
+    /// Sets the objective target below which the solve stops (`ftarget`).
+    pub fn f_target(mut self, f_target: f64) -> Self {
+        self.raw.ftarget = f_target;
+        self
+    }
+
+    /// Sets the number of interpolation points (`npt`), used by NEWUOA,
+    /// BOBYQA and LINCOA.
+    pub fn npt(mut self, npt: i32) -> Self {
+        self.raw.npt = npt;
+        self
+    }
+
+    /// Sets the constraint-violation tolerance (`ctol`).
+    pub fn ctol(mut self, ctol: f64) -> Self {
+        self.raw.ctol = ctol;
+        self
+    }
+
+    /// Sets the progress-printing verbosity (`iprint`).
+    pub fn print(mut self, level: MsgLevel) -> Self {
+        self.raw.iprint = level.to_raw();
+        self
+    }
+
+    /// Sets the intermediate callback invoked after each iteration (`callback`).
+    pub fn callback(mut self, callback: prima_callback_t) -> Self {
+        self.raw.callback = callback;
+        self
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One of PRIMA's derivative-free algorithms, plus [`Algorithm::Auto`] which
+/// lets [`solve`](Problem::solve) pick the right one from the problem's
+/// declared constraints.
+///
+/// Each concrete algorithm is valid only for certain constraint classes;
+/// [`solve`](Problem::solve) validates the choice and returns an [`Error`] when
+/// an explicit algorithm cannot handle the problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Unconstrained optimization by quadratic approximation, for small `n`.
+    Uobyqa,
+    /// Unconstrained optimization for larger `n`.
+    Newuoa,
+    /// Bound-constrained optimization.
+    Bobyqa,
+    /// Linearly-constrained optimization.
+    Lincoa,
+    /// Nonlinearly-constrained optimization.
+    Cobyla,
+    /// Select the algorithm automatically from the problem structure.
+    Auto,
+}
+
+impl Algorithm {
+    /// Converts to the raw `prima_algorithm_t` value.
+    fn to_raw(self) -> prima_algorithm_t {
+        match self {
+            Algorithm::Uobyqa => prima_algorithm_t_PRIMA_UOBYQA,
+            Algorithm::Newuoa => prima_algorithm_t_PRIMA_NEWUOA,
+            Algorithm::Bobyqa => prima_algorithm_t_PRIMA_BOBYQA,
+            Algorithm::Lincoa => prima_algorithm_t_PRIMA_LINCOA,
+            Algorithm::Cobyla => prima_algorithm_t_PRIMA_COBYLA,
+            // `Auto` is resolved to a concrete algorithm before this is called.
+            Algorithm::Auto => prima_algorithm_t_PRIMA_COBYLA,
+        }
+    }
+}
+
+/// The constraint classes a problem declares, used to select and validate an
+/// [`Algorithm`].
+struct Structure {
+    nonlinear: bool,
+    linear: bool,
+    bounds: bool,
+    n: i32,
+}
+
+impl Structure {
+    /// Picks the least general algorithm that can handle this structure.
+    fn auto(&self) -> Algorithm {
+        if self.nonlinear {
+            Algorithm::Cobyla
+        } else if self.linear {
+            Algorithm::Lincoa
+        } else if self.bounds {
+            Algorithm::Bobyqa
+        } else if self.n > 1 {
+            Algorithm::Newuoa
+        } else {
+            Algorithm::Uobyqa
+        }
+    }
+
+    /// Returns whether `algorithm` can handle this structure.
+    fn supports(&self, algorithm: Algorithm) -> bool {
+        match algorithm {
+            // COBYLA handles every constraint class.
+            Algorithm::Cobyla | Algorithm::Auto => true,
+            // LINCOA handles linear constraints and bounds, but not nonlinear.
+            Algorithm::Lincoa => !self.nonlinear,
+            // BOBYQA handles bounds only.
+            Algorithm::Bobyqa => !self.nonlinear && !self.linear,
+            // NEWUOA and UOBYQA are unconstrained-only.
+            Algorithm::Newuoa | Algorithm::Uobyqa => {
+                !self.nonlinear && !self.linear && !self.bounds
+            }
+        }
+    }
+}
+
+/// Errors returned by [`solve`](Problem::solve).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The explicitly chosen [`Algorithm`] cannot handle the constraints the
+    /// problem declares.
+    IncompatibleAlgorithm(Algorithm),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IncompatibleAlgorithm(algorithm) => write!(
+                f,
+                "algorithm {:?} is incompatible with the problem's declared constraints",
+                algorithm
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A fluent builder for [`prima_problem_t`].
+///
+/// The builder owns the backing `Vec`s for every array-valued field and the
+/// boxed objective and constraint closures. Their pointers are wired into the
+/// raw struct only inside [`solve`](Problem::solve), so no dangling pointer can
+/// escape the builder.
+///
+/// ```rust
+/// # use prima_sys::safe::{Algorithm, Problem};
+/// let solution = Problem::new(2)
+///     .x0(&[0.0, 0.0])
+///     .bounds(&[-10.0, -10.0], &[10.0, 10.0])
+///     .objective(|x| (x[0] - 5.0).powi(2) + (x[1] - 4.0).powi(2))
+///     .solve(Algorithm::Auto, Default::default())
+///     .unwrap();
+/// println!("{:?}", solution);
+/// ```
+pub struct Problem {
+    n: i32,
+    x0: Option<Vec<f64>>,
+    xl: Option<Vec<f64>>,
+    xu: Option<Vec<f64>>,
+    a_ineq: Option<Vec<f64>>,
+    b_ineq: Option<Vec<f64>>,
+    a_eq: Option<Vec<f64>>,
+    b_eq: Option<Vec<f64>>,
+    m_nlcon: i32,
+    objective: Option<BoxedObjective>,
+    nonlinear: Option<Box<dyn FnMut(&[f64]) -> Vec<f64>>>,
+}
+
+impl Problem {
+    /// Creates a builder for a problem with `n` variables.
+    pub fn new(n: i32) -> Self {
+        Self {
+            n,
+            x0: None,
+            xl: None,
+            xu: None,
+            a_ineq: None,
+            b_ineq: None,
+            a_eq: None,
+            b_eq: None,
+            m_nlcon: 0,
+            objective: None,
+            nonlinear: None,
+        }
+    }
+
+    /// Sets the initial guess. Defaults to all zeros if never called.
+    pub fn x0(mut self, x0: &[f64]) -> Self {
+        self.x0 = Some(x0.to_vec());
+        self
+    }
+
+    /// Sets the lower and upper bounds on the variables (`xl`/`xu`).
+    pub fn bounds(mut self, xl: &[f64], xu: &[f64]) -> Self {
+        self.xl = Some(xl.to_vec());
+        self.xu = Some(xu.to_vec());
+        self
+    }
+
+    /// Sets the linear inequality constraints `Aineq x <= bineq`. `a` is stored
+    /// row-major with `b.len()` rows of `n` columns.
+    pub fn linear_ineq(mut self, a: &[f64], b: &[f64]) -> Self {
+        self.a_ineq = Some(a.to_vec());
+        self.b_ineq = Some(b.to_vec());
+        self
+    }
+
+    /// Sets the linear equality constraints `Aeq x = beq`. `a` is stored
+    /// row-major with `b.len()` rows of `n` columns.
+    pub fn linear_eq(mut self, a: &[f64], b: &[f64]) -> Self {
+        self.a_eq = Some(a.to_vec());
+        self.b_eq = Some(b.to_vec());
+        self
+    }
+
+    /// Sets the objective closure `FnMut(&[f64]) -> f64`.
+    pub fn objective<F>(mut self, objective: F) -> Self
+    where
+        F: FnMut(&[f64]) -> f64 + 'static,
+    {
+        self.objective = Some(Box::new(objective));
+        self
+    }
+
+    /// Adds `m` nonlinear constraints, evaluated by the closure
+    /// `FnMut(&[f64]) -> Vec<f64>` following PRIMA's `constr <= 0` convention.
+    pub fn nonlinear<F>(mut self, m: i32, constraints: F) -> Self
+    where
+        F: FnMut(&[f64]) -> Vec<f64> + 'static,
+    {
+        self.m_nlcon = m;
+        self.nonlinear = Some(Box::new(constraints));
+        self
+    }
+
+    /// Runs PRIMA on the built problem with the given algorithm and options,
+    /// wiring every owned buffer and closure into the raw structs for the
+    /// duration of the call.
+    ///
+    /// With [`Algorithm::Auto`] the algorithm is chosen from the declared
+    /// constraints; with an explicit algorithm the choice is validated against
+    /// them and an [`Error::IncompatibleAlgorithm`] is returned if it cannot
+    /// handle the problem.
+    ///
+    /// ## Panics
+    /// Panics if no objective closure has been set.
+    pub fn solve(mut self, algorithm: Algorithm, options: Options) -> Result<Solution, Error> {
+        let n = self.n;
+
+        let structure = Structure {
+            nonlinear: self.m_nlcon > 0,
+            linear: self.a_ineq.is_some() || self.a_eq.is_some(),
+            bounds: self.xl.is_some() || self.xu.is_some(),
+            n,
+        };
+        let algorithm = if algorithm == Algorithm::Auto {
+            structure.auto()
+        } else if structure.supports(algorithm) {
+            algorithm
+        } else {
+            return Err(Error::IncompatibleAlgorithm(algorithm));
+        };
+        let raw_algorithm = algorithm.to_raw();
+
+        let x0 = self.x0.get_or_insert_with(|| vec![0.0; n as usize]);
+        let x0_values = x0.clone();
+
+        let mut problem = new_problem(n);
+        problem.x0 = x0.as_mut_ptr();
+
+        if let Some(xl) = self.xl.as_mut() {
+            problem.xl = xl.as_mut_ptr();
+        }
+        if let Some(xu) = self.xu.as_mut() {
+            problem.xu = xu.as_mut_ptr();
+        }
+        if let (Some(a), Some(b)) = (self.a_ineq.as_mut(), self.b_ineq.as_mut()) {
+            problem.m_ineq = b.len() as i32;
+            problem.Aineq = a.as_mut_ptr();
+            problem.bineq = b.as_mut_ptr();
+        }
+        if let (Some(a), Some(b)) = (self.a_eq.as_mut(), self.b_eq.as_mut()) {
+            problem.m_eq = b.len() as i32;
+            problem.Aeq = a.as_mut_ptr();
+            problem.beq = b.as_mut_ptr();
+        }
+
+        let mut raw_options = options.raw;
+
+        let objective = self
+            .objective
+            .take()
+            .expect("an objective closure must be set before calling solve");
+
+        // The boxed trampoline data must outlive the `prima_minimize` call, so
+        // it is held in locals that are only dropped at the end of the function.
+        let mut objective_data;
+        let mut constraints_data;
+        let mut nlconstr0;
+
+        // COBYLA is driven through `calcfc`, so it takes the constrained path
+        // even when no nonlinear constraints are declared.
+        let nonlinear = self.nonlinear.take().or_else(|| {
+            (algorithm == Algorithm::Cobyla).then(|| {
+                Box::new(|_: &[f64]| Vec::new()) as Box<dyn FnMut(&[f64]) -> Vec<f64>>
+            })
+        });
+
+        if let Some(mut nonlinear) = nonlinear {
+            let mut objective = objective;
+            let combined: BoxedObjectiveAndConstraints =
+                Box::new(move |x| (objective(x), nonlinear(x)));
+            constraints_data = Box::new(ObjectiveAndConstraints {
+                call: combined,
+                n: n as usize,
+                m: self.m_nlcon as usize,
+            });
+
+            // PRIMA needs the objective and constraint values at the initial point.
+            let (f0, constr0) = (constraints_data.call)(&x0_values);
+            nlconstr0 = constr0;
+            problem.f0 = f0;
+            problem.nlconstr0 = nlconstr0.as_mut_ptr();
+            problem.m_nlcon = self.m_nlcon;
+            problem.calcfc = Some(calcfc_trampoline::<BoxedObjectiveAndConstraints>);
+            raw_options.data =
+                (&mut *constraints_data as *mut ObjectiveAndConstraints<BoxedObjectiveAndConstraints>)
+                    .cast::<c_void>();
+
+            let mut result = prima_result_t::default();
+            let result_ptr = std::ptr::addr_of_mut!(result);
+            unsafe {
+                prima_minimize(raw_algorithm, problem, raw_options, result_ptr);
+            }
+            Ok(Solution::from_raw(result, n as usize, self.m_nlcon as usize))
+        } else {
+            objective_data = Box::new(Objective {
+                call: objective,
+                n: n as usize,
+            });
+            problem.calfun = Some(calfun_trampoline::<BoxedObjective>);
+            raw_options.data =
+                (&mut *objective_data as *mut Objective<BoxedObjective>).cast::<c_void>();
+
+            let mut result = prima_result_t::default();
+            let result_ptr = std::ptr::addr_of_mut!(result);
+            unsafe {
+                prima_minimize(raw_algorithm, problem, raw_options, result_ptr);
+            }
+            Ok(Solution::from_raw(result, n as usize, 0))
+        }
+    }
+}
+
+/// The outcome of a solve, mapped from the raw `status` code (`prima_rc_t`).
+///
+/// Any code not recognized here is preserved in [`Status::Unknown`] so no
+/// information is lost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// The trust-region radius reached its floor (`PRIMA_SMALL_TR_RADIUS`).
+    SmallTrustRegion,
+    /// The objective target was achieved (`PRIMA_FTARGET_ACHIEVED`).
+    FTargetAchieved,
+    /// The trust-region subproblem solver failed (`PRIMA_TRSUBP_FAILED`).
+    TrustRegionSubproblemFailed,
+    /// The function-evaluation budget was exhausted (`PRIMA_MAXFUN_REACHED`).
+    MaxFunEvalsReached,
+    /// The trust-region iteration budget was exhausted (`PRIMA_MAXTR_REACHED`).
+    MaxTrustRegionItersReached,
+    /// A NaN or infinity appeared in `x` (`PRIMA_NAN_INF_X`).
+    NanInfX,
+    /// A NaN or infinity appeared in the objective (`PRIMA_NAN_INF_F`).
+    NanInfF,
+    /// A NaN or infinity appeared in the model (`PRIMA_NAN_INF_MODEL`).
+    NanInfModel,
+    /// There is no room between the bounds (`PRIMA_NO_SPACE_BETWEEN_BOUNDS`).
+    NoSpaceBetweenBounds,
+    /// Rounding errors became damaging (`PRIMA_DAMAGING_ROUNDING`).
+    DamagingRounding,
+    /// A linear constraint was all zeros (`PRIMA_ZERO_LINEAR_CONSTRAINT`).
+    ZeroLinearConstraint,
+    /// The user callback requested termination (`PRIMA_CALLBACK_TERMINATE`).
+    CallbackTerminate,
+    /// The input was invalid (`PRIMA_INVALID_INPUT`).
+    InvalidInput,
+    /// An internal assertion failed (`PRIMA_ASSERTION_FAILS`).
+    AssertionFails,
+    /// Input validation failed (`PRIMA_VALIDATION_FAILS`).
+    ValidationFails,
+    /// Memory allocation failed (`PRIMA_MEMORY_ALLOCATION_FAILS`).
+    MemoryAllocationFails,
+    /// The options pointer was null (`PRIMA_NULL_OPTIONS`).
+    NullOptions,
+    /// The problem pointer was null (`PRIMA_NULL_PROBLEM`).
+    NullProblem,
+    /// The initial guess pointer was null (`PRIMA_NULL_X0`).
+    NullX0,
+    /// The result pointer was null (`PRIMA_NULL_RESULT`).
+    NullResult,
+    /// The objective function pointer was null (`PRIMA_NULL_FUNCTION`).
+    NullFunction,
+    /// A status code not otherwise recognized, preserved verbatim.
+    Unknown(i32),
+}
+
+impl Status {
+    /// Maps a raw `prima_rc_t` status code to a [`Status`].
+    fn from_raw(status: i32) -> Self {
+        match status {
+            prima_rc_t_PRIMA_SMALL_TR_RADIUS => Status::SmallTrustRegion,
+            prima_rc_t_PRIMA_FTARGET_ACHIEVED => Status::FTargetAchieved,
+            prima_rc_t_PRIMA_TRSUBP_FAILED => Status::TrustRegionSubproblemFailed,
+            prima_rc_t_PRIMA_MAXFUN_REACHED => Status::MaxFunEvalsReached,
+            prima_rc_t_PRIMA_MAXTR_REACHED => Status::MaxTrustRegionItersReached,
+            prima_rc_t_PRIMA_NAN_INF_X => Status::NanInfX,
+            prima_rc_t_PRIMA_NAN_INF_F => Status::NanInfF,
+            prima_rc_t_PRIMA_NAN_INF_MODEL => Status::NanInfModel,
+            prima_rc_t_PRIMA_NO_SPACE_BETWEEN_BOUNDS => Status::NoSpaceBetweenBounds,
+            prima_rc_t_PRIMA_DAMAGING_ROUNDING => Status::DamagingRounding,
+            prima_rc_t_PRIMA_ZERO_LINEAR_CONSTRAINT => Status::ZeroLinearConstraint,
+            prima_rc_t_PRIMA_CALLBACK_TERMINATE => Status::CallbackTerminate,
+            prima_rc_t_PRIMA_INVALID_INPUT => Status::InvalidInput,
+            prima_rc_t_PRIMA_ASSERTION_FAILS => Status::AssertionFails,
+            prima_rc_t_PRIMA_VALIDATION_FAILS => Status::ValidationFails,
+            prima_rc_t_PRIMA_MEMORY_ALLOCATION_FAILS => Status::MemoryAllocationFails,
+            prima_rc_t_PRIMA_NULL_OPTIONS => Status::NullOptions,
+            prima_rc_t_PRIMA_NULL_PROBLEM => Status::NullProblem,
+            prima_rc_t_PRIMA_NULL_X0 => Status::NullX0,
+            prima_rc_t_PRIMA_NULL_RESULT => Status::NullResult,
+            prima_rc_t_PRIMA_NULL_FUNCTION => Status::NullFunction,
+            other => Status::Unknown(other),
+        }
+    }
+}
+
+/// An owning result of a solve.
+///
+/// All of the pointer-valued fields of the raw `prima_result_t` are copied into
+/// owned Rust values at construction: `x` becomes a `Vec<f64>`, the nonlinear
+/// constraint values become a `Vec<f64>`, and the C `message` string becomes a
+/// [`String`]. The raw result is retained and released with `prima_free_result`
+/// when the `Solution` is dropped.
+pub struct Solution {
+    status: Status,
+    success: bool,
+    f: f64,
+    cstrv: f64,
+    nf: i32,
+    x: Vec<f64>,
+    nlconstr: Vec<f64>,
+    message: String,
+    raw: prima_result_t,
+}
+
+impl Solution {
+    /// Builds an owning [`Solution`] from a raw result with `n` variables and
+    /// `m` nonlinear constraints, taking ownership of the raw result's
+    /// allocations.
+    fn from_raw(raw: prima_result_t, n: usize, m: usize) -> Self {
+        let x = if raw.x.is_null() {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(raw.x, n).to_vec() }
+        };
+        let nlconstr = if raw.nlconstr.is_null() || m == 0 {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(raw.nlconstr, m).to_vec() }
+        };
+        let message = if raw.message.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(raw.message) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        Solution {
+            status: Status::from_raw(raw.status),
+            success: raw.success,
+            f: raw.f,
+            cstrv: raw.cstrv,
+            nf: raw.nf,
+            x,
+            nlconstr,
+            message,
+            raw,
+        }
+    }
+
+    /// The computed optimum.
+    pub fn x(&self) -> &[f64] {
+        &self.x
+    }
+
+    /// The objective value at the optimum.
+    pub fn objective(&self) -> f64 {
+        self.f
+    }
+
+    /// The constraint violation at the optimum.
+    pub fn constraint_violation(&self) -> f64 {
+        self.cstrv
+    }
+
+    /// The nonlinear constraint values at the optimum.
+    pub fn nonlinear_constraints(&self) -> &[f64] {
+        &self.nlconstr
+    }
+
+    /// The number of function evaluations performed.
+    pub fn num_evals(&self) -> i32 {
+        self.nf
+    }
+
+    /// The mapped exit status.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Whether PRIMA considers the solve successful.
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// The human-readable message describing the exit status.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Debug for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Solution")
+            .field("status", &self.status)
+            .field("f", &self.f)
+            .field("nf", &self.nf)
+            .field("x", &self.x)
+            .finish()
+    }
+}
+
+impl Drop for Solution {
+    fn drop(&mut self) {
+        unsafe {
+            prima_free_result(std::ptr::addr_of_mut!(self.raw));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(nonlinear: bool, linear: bool, bounds: bool, n: i32) -> Structure {
+        Structure {
+            nonlinear,
+            linear,
+            bounds,
+            n,
+        }
+    }
+
+    #[test]
+    fn auto_selects_cobyla_for_nonlinear_constraints() {
+        assert_eq!(structure(true, true, true, 3).auto(), Algorithm::Cobyla);
+        assert_eq!(structure(true, false, false, 3).auto(), Algorithm::Cobyla);
+    }
+
+    #[test]
+    fn auto_selects_lincoa_for_linear_constraints() {
+        assert_eq!(structure(false, true, true, 3).auto(), Algorithm::Lincoa);
+    }
+
+    #[test]
+    fn auto_selects_bobyqa_for_bounds() {
+        assert_eq!(structure(false, false, true, 3).auto(), Algorithm::Bobyqa);
+    }
+
+    #[test]
+    fn auto_splits_unconstrained_by_dimension() {
+        assert_eq!(structure(false, false, false, 2).auto(), Algorithm::Newuoa);
+        assert_eq!(structure(false, false, false, 1).auto(), Algorithm::Uobyqa);
+    }
+
+    #[test]
+    fn supports_accepts_compatible_algorithms() {
+        assert!(structure(true, true, true, 3).supports(Algorithm::Cobyla));
+        assert!(structure(false, true, true, 3).supports(Algorithm::Lincoa));
+        assert!(structure(false, false, true, 3).supports(Algorithm::Bobyqa));
+        assert!(structure(false, false, false, 3).supports(Algorithm::Newuoa));
+        assert!(structure(false, false, false, 1).supports(Algorithm::Uobyqa));
+    }
+
+    #[test]
+    fn supports_rejects_incompatible_algorithms() {
+        // NEWUOA and UOBYQA are unconstrained-only.
+        assert!(!structure(false, false, true, 3).supports(Algorithm::Newuoa));
+        assert!(!structure(false, false, true, 1).supports(Algorithm::Uobyqa));
+        // BOBYQA cannot take linear or nonlinear constraints.
+        assert!(!structure(false, true, false, 3).supports(Algorithm::Bobyqa));
+        // LINCOA cannot take nonlinear constraints.
+        assert!(!structure(true, false, false, 3).supports(Algorithm::Lincoa));
+    }
+
+    #[test]
+    fn solve_rejects_incompatible_explicit_algorithm() {
+        // NEWUOA-with-bounds is the canonical mismatch the validation guards.
+        let result = Problem::new(2)
+            .bounds(&[-1.0, -1.0], &[1.0, 1.0])
+            .objective(|x| x[0] * x[0] + x[1] * x[1])
+            .solve(Algorithm::Newuoa, Default::default());
+        assert_eq!(
+            result.err(),
+            Some(Error::IncompatibleAlgorithm(Algorithm::Newuoa))
+        );
+    }
+
+    #[test]
+    fn status_from_raw_maps_every_known_code() {
+        let cases = [
+            (prima_rc_t_PRIMA_SMALL_TR_RADIUS, Status::SmallTrustRegion),
+            (prima_rc_t_PRIMA_FTARGET_ACHIEVED, Status::FTargetAchieved),
+            (
+                prima_rc_t_PRIMA_TRSUBP_FAILED,
+                Status::TrustRegionSubproblemFailed,
+            ),
+            (prima_rc_t_PRIMA_MAXFUN_REACHED, Status::MaxFunEvalsReached),
+            (
+                prima_rc_t_PRIMA_MAXTR_REACHED,
+                Status::MaxTrustRegionItersReached,
+            ),
+            (prima_rc_t_PRIMA_NAN_INF_X, Status::NanInfX),
+            (prima_rc_t_PRIMA_NAN_INF_F, Status::NanInfF),
+            (prima_rc_t_PRIMA_NAN_INF_MODEL, Status::NanInfModel),
+            (
+                prima_rc_t_PRIMA_NO_SPACE_BETWEEN_BOUNDS,
+                Status::NoSpaceBetweenBounds,
+            ),
+            (prima_rc_t_PRIMA_DAMAGING_ROUNDING, Status::DamagingRounding),
+            (
+                prima_rc_t_PRIMA_ZERO_LINEAR_CONSTRAINT,
+                Status::ZeroLinearConstraint,
+            ),
+            (prima_rc_t_PRIMA_CALLBACK_TERMINATE, Status::CallbackTerminate),
+            (prima_rc_t_PRIMA_INVALID_INPUT, Status::InvalidInput),
+            (prima_rc_t_PRIMA_ASSERTION_FAILS, Status::AssertionFails),
+            (prima_rc_t_PRIMA_VALIDATION_FAILS, Status::ValidationFails),
+            (
+                prima_rc_t_PRIMA_MEMORY_ALLOCATION_FAILS,
+                Status::MemoryAllocationFails,
+            ),
+            (prima_rc_t_PRIMA_NULL_OPTIONS, Status::NullOptions),
+            (prima_rc_t_PRIMA_NULL_PROBLEM, Status::NullProblem),
+            (prima_rc_t_PRIMA_NULL_X0, Status::NullX0),
+            (prima_rc_t_PRIMA_NULL_RESULT, Status::NullResult),
+            (prima_rc_t_PRIMA_NULL_FUNCTION, Status::NullFunction),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(Status::from_raw(code), expected);
+        }
+    }
+
+    #[test]
+    fn status_from_raw_preserves_unrecognized_code() {
+        assert_eq!(Status::from_raw(12345), Status::Unknown(12345));
+    }
+}